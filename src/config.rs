@@ -0,0 +1,209 @@
+use crate::retry::RetryPolicy;
+use ethers::types::Address;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+/// Aggregates every missing or invalid environment variable encountered
+/// while building a [`Config`], instead of panicking on the first one.
+#[derive(Debug)]
+pub struct ConfigError {
+    errors: Vec<String>,
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "invalid configuration:")?;
+        for error in &self.errors {
+            writeln!(f, "  - {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Typed, validated service configuration, built once at startup from
+/// environment variables so the rest of the service never touches
+/// `std::env` directly.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub rpc_url: String,
+    pub contract_address: Address,
+    /// How often the scan loop polls for new blocks.
+    pub poll_interval: Duration,
+    /// How long to wait after a `JobOpened` event before checking the
+    /// enclave, to give it time to boot.
+    pub enclave_warmup: Duration,
+    /// Trailing blocks re-scanned every tick before being checkpointed.
+    pub confirmation_depth: u64,
+    /// Image URLs a job's metadata must reference to be monitored.
+    pub allowed_image_urls: Vec<String>,
+    /// Base URL of the operator refresh API; `/operators/jobs/refresh/ArbOne/<job>`
+    /// is appended per job.
+    pub refresh_base_url: String,
+    /// Address the Prometheus `/metrics` server binds to.
+    pub metrics_addr: SocketAddr,
+    /// Path to the notifier config file, if notifications are enabled.
+    pub notifier_config_path: Option<String>,
+    /// Shared backoff policy for the reachability and refresh-API retry paths.
+    pub retry_policy: RetryPolicy,
+}
+
+const DEFAULT_POLL_INTERVAL_SECS: u64 = 10;
+const DEFAULT_ENCLAVE_WARMUP_SECS: u64 = 180;
+const DEFAULT_CONFIRMATION_DEPTH: u64 = 5;
+const DEFAULT_REFRESH_BASE_URL: &str = "https://sk.arb1.marlin.org";
+const DEFAULT_METRICS_ADDR: &str = "0.0.0.0:9898";
+const DEFAULT_ALLOWED_IMAGE_URLS: &[&str] = &[
+    "https://artifacts.marlin.org/oyster/eifs/base-blue_v3.0.0_linux_amd64.eif",
+    "https://artifacts.marlin.org/oyster/eifs/base-blue_v3.0.0_linux_arm64.eif",
+];
+const DEFAULT_RETRY_MAX_ATTEMPTS: u32 = 3;
+const DEFAULT_RETRY_BASE_DELAY_SECS: u64 = 5;
+const DEFAULT_RETRY_MULTIPLIER: f64 = 2.0;
+const DEFAULT_RETRY_MAX_DELAY_SECS: u64 = 60;
+
+impl Config {
+    /// Reads and validates every setting from the environment, returning a
+    /// single [`ConfigError`] listing every problem found rather than
+    /// failing on the first one.
+    pub fn from_env() -> Result<Config, ConfigError> {
+        let mut errors = Vec::new();
+
+        let rpc_url = required_var("RPC_URL", &mut errors);
+
+        let contract_address = required_var("CONTRACT_ADDRESS", &mut errors)
+            .and_then(|raw| parse_field("CONTRACT_ADDRESS", &raw, &mut errors));
+
+        let poll_interval = duration_var(
+            "POLL_INTERVAL_SECS",
+            DEFAULT_POLL_INTERVAL_SECS,
+            &mut errors,
+        );
+
+        let enclave_warmup = duration_var(
+            "ENCLAVE_WARMUP_SECS",
+            DEFAULT_ENCLAVE_WARMUP_SECS,
+            &mut errors,
+        );
+
+        let confirmation_depth = u64_var(
+            "CONFIRMATION_DEPTH",
+            DEFAULT_CONFIRMATION_DEPTH,
+            &mut errors,
+        );
+
+        let allowed_image_urls = std::env::var("ALLOWED_IMAGE_URLS")
+            .map(|raw| raw.split(',').map(|s| s.trim().to_string()).collect())
+            .unwrap_or_else(|_| {
+                DEFAULT_ALLOWED_IMAGE_URLS
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect()
+            });
+
+        let refresh_base_url = std::env::var("REFRESH_BASE_URL")
+            .unwrap_or_else(|_| DEFAULT_REFRESH_BASE_URL.to_string());
+
+        let metrics_addr = socket_addr_var("METRICS_ADDR", DEFAULT_METRICS_ADDR, &mut errors);
+
+        let notifier_config_path = std::env::var("NOTIFIER_CONFIG_PATH").ok();
+
+        let retry_max_attempts = u32_var(
+            "RETRY_MAX_ATTEMPTS",
+            DEFAULT_RETRY_MAX_ATTEMPTS,
+            &mut errors,
+        );
+        let retry_base_delay = duration_var(
+            "RETRY_BASE_DELAY_SECS",
+            DEFAULT_RETRY_BASE_DELAY_SECS,
+            &mut errors,
+        );
+        let retry_multiplier = f64_var("RETRY_MULTIPLIER", DEFAULT_RETRY_MULTIPLIER, &mut errors);
+        let retry_max_delay = duration_var(
+            "RETRY_MAX_DELAY_SECS",
+            DEFAULT_RETRY_MAX_DELAY_SECS,
+            &mut errors,
+        );
+
+        if !errors.is_empty() {
+            return Err(ConfigError { errors });
+        }
+
+        Ok(Config {
+            rpc_url: rpc_url.expect("validated above"),
+            contract_address: contract_address.expect("validated above"),
+            poll_interval: poll_interval.expect("validated above"),
+            enclave_warmup: enclave_warmup.expect("validated above"),
+            confirmation_depth: confirmation_depth.expect("validated above"),
+            allowed_image_urls,
+            refresh_base_url,
+            metrics_addr: metrics_addr.expect("validated above"),
+            notifier_config_path,
+            retry_policy: RetryPolicy {
+                max_attempts: retry_max_attempts.expect("validated above"),
+                base_delay: retry_base_delay.expect("validated above"),
+                multiplier: retry_multiplier.expect("validated above"),
+                max_delay: retry_max_delay.expect("validated above"),
+            },
+        })
+    }
+}
+
+fn required_var(name: &str, errors: &mut Vec<String>) -> Option<String> {
+    match std::env::var(name) {
+        Ok(value) => Some(value),
+        Err(_) => {
+            errors.push(format!("{} must be set", name));
+            None
+        }
+    }
+}
+
+fn parse_field<T: std::str::FromStr>(name: &str, raw: &str, errors: &mut Vec<String>) -> Option<T>
+where
+    T::Err: std::fmt::Display,
+{
+    match raw.parse() {
+        Ok(value) => Some(value),
+        Err(e) => {
+            errors.push(format!("{} is invalid: {}", name, e));
+            None
+        }
+    }
+}
+
+fn duration_var(name: &str, default_secs: u64, errors: &mut Vec<String>) -> Option<Duration> {
+    match std::env::var(name) {
+        Ok(raw) => parse_field::<u64>(name, &raw, errors).map(Duration::from_secs),
+        Err(_) => Some(Duration::from_secs(default_secs)),
+    }
+}
+
+fn u64_var(name: &str, default: u64, errors: &mut Vec<String>) -> Option<u64> {
+    match std::env::var(name) {
+        Ok(raw) => parse_field(name, &raw, errors),
+        Err(_) => Some(default),
+    }
+}
+
+fn u32_var(name: &str, default: u32, errors: &mut Vec<String>) -> Option<u32> {
+    match std::env::var(name) {
+        Ok(raw) => parse_field(name, &raw, errors),
+        Err(_) => Some(default),
+    }
+}
+
+fn f64_var(name: &str, default: f64, errors: &mut Vec<String>) -> Option<f64> {
+    match std::env::var(name) {
+        Ok(raw) => parse_field(name, &raw, errors),
+        Err(_) => Some(default),
+    }
+}
+
+fn socket_addr_var(name: &str, default: &str, errors: &mut Vec<String>) -> Option<SocketAddr> {
+    match std::env::var(name) {
+        Ok(raw) => parse_field(name, &raw, errors),
+        Err(_) => Some(default.parse().expect("default socket address must be valid")),
+    }
+}
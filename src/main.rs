@@ -1,6 +1,10 @@
+mod config;
 mod db;
+mod metrics;
 mod models;
+mod notifier;
 mod reachability;
+mod retry;
 mod schema;
 mod types;
 
@@ -9,11 +13,16 @@ use ethers::prelude::*;
 use ethers::providers::{Http, Provider};
 use log::{error, info};
 use std::sync::Arc;
-use std::time::Duration as StdDuration;
 
+use config::Config;
 use db::establish_connection_pool;
-use models::{NewOperatorEndpointError, NewReachabilityError};
+use models::{
+    JobCheck, JobCheckState, NewOperatorEndpointError, NewReachabilityError, ProcessedLog,
+    ScanState,
+};
+use notifier::{FailureKind, MonitorEvent};
 use reachability::check_reachability;
+use retry::retry_async;
 use types::Metadata;
 
 use crate::reachability::wait_for_ip_address;
@@ -26,26 +35,49 @@ async fn main() -> anyhow::Result<()> {
 
     dotenvy::dotenv().ok();
 
+    let config = Arc::new(Config::from_env()?);
+    let contract_address_str = format!("{:?}", config.contract_address);
+
     // Establish database connection pool
     let pool = establish_connection_pool();
     info!("Database connection pool established");
 
-    let rpc_url = std::env::var("RPC_URL").expect("RPC_URL must be set in .env file");
+    let notifiers = Arc::new(notifier::load(config.notifier_config_path.as_deref()));
+    info!("{} notifier(s) configured", notifiers.len());
+
+    metrics::start(config.metrics_addr);
 
-    let provider = Provider::<Http>::try_from(rpc_url)?;
+    let provider = Provider::<Http>::try_from(config.rpc_url.clone())?;
     let provider = Arc::new(provider);
 
-    let contract_address_str =
-        std::env::var("CONTRACT_ADDRESS").expect("CONTRACT_ADDRESS must be set in .env file");
-    let contract_addr: Address = contract_address_str.parse()?;
-    let contract = MarketV1::new(contract_addr, provider.clone());
+    let contract = MarketV1::new(config.contract_address, provider.clone());
+
+    let chain_id = provider.get_chainid().await?.as_u64() as i64;
+
+    // Resume from the persisted checkpoint so a restart doesn't lose
+    // `JobOpened` events emitted while the service was down. Seed with the
+    // current head if this is the first run for this chain/contract.
+    let mut last_checked_block = {
+        let mut conn = pool.get()?;
+        match ScanState::load(&mut conn, chain_id, &contract_address_str)? {
+            Some(state) => {
+                let resume_from = U64::from(state.last_checked_block as u64);
+                info!("Resuming from persisted checkpoint: block {}", resume_from);
+                resume_from
+            }
+            None => {
+                let head = provider.get_block_number().await?;
+                info!("No checkpoint found. Seeding from current head: {}", head);
+                ScanState::save(&mut conn, chain_id, &contract_address_str, head.as_u64() as i64)?;
+                head
+            }
+        }
+    };
 
-    // Get the current block number to start from
-    let mut last_checked_block = provider.get_block_number().await?;
-    info!("Starting from block number: {}", last_checked_block);
+    metrics::LAST_CHECKED_BLOCK.set(last_checked_block.as_u64() as i64);
 
-    // Poll for new blocks every 10 seconds
-    let mut interval = tokio::time::interval(StdDuration::from_secs(10));
+    // Poll for new blocks at the configured interval
+    let mut interval = tokio::time::interval(config.poll_interval);
 
     loop {
         interval.tick().await;
@@ -69,12 +101,18 @@ async fn main() -> anyhow::Result<()> {
             current_block
         );
 
-        // Query for JobOpened events in the new blocks
+        // Only checkpoint up to `current_block - confirmation_depth`; the
+        // trailing blocks are re-scanned every tick until they're confirmed.
+        let safe_block = current_block.saturating_sub(U64::from(config.confirmation_depth));
+
+        // Query for JobOpened events in the new blocks, including logs with
+        // their position so duplicate rescans of the trailing window can be
+        // filtered out below.
         let events = match contract
             .event::<JobOpenedFilter>()
             .from_block(last_checked_block + 1)
             .to_block(current_block)
-            .query()
+            .query_with_meta()
             .await
         {
             Ok(events) => events,
@@ -91,11 +129,38 @@ async fn main() -> anyhow::Result<()> {
             current_block
         );
 
-        for event in events {
+        for (event, meta) in events {
+            let job = "0x".to_string() + &hex::encode(event.job);
+            let block_number = meta.block_number.as_u64() as i64;
+            let log_index = meta.log_index.as_u64() as i64;
+
+            // Persist the dedup marker so the trailing confirmation window,
+            // re-scanned on every tick and again after a restart, doesn't
+            // re-spawn work for an event already handled.
+            let mut dedup_conn = match pool.get() {
+                Ok(conn) => conn,
+                Err(e) => {
+                    error!("Failed to get DB connection for dedup check: {}", e);
+                    continue;
+                }
+            };
+            match ProcessedLog::try_mark_processed(&mut dedup_conn, &job, block_number, log_index)
+            {
+                Ok(true) => {}
+                Ok(false) => {
+                    info!("Skipping already-processed log for job {}", job);
+                    continue;
+                }
+                Err(e) => {
+                    error!("Failed to record processed log for job {}: {}", job, e);
+                    continue;
+                }
+            }
+
             info!("JobOpened event found");
+            metrics::JOBS_OBSERVED_TOTAL.inc();
             let metadata_str = event.metadata;
             let owner = event.owner;
-            let job = "0x".to_string() + &hex::encode(event.job);
             let operator = event.provider;
             let cp_url = match contract.providers(operator).call().await {
                 Ok(url) => url,
@@ -118,12 +183,7 @@ async fn main() -> anyhow::Result<()> {
 
             // Check if the URL matches the allowed blue images
             if let Some(url) = &metadata.url {
-                let allowed_urls = [
-                    "https://artifacts.marlin.org/oyster/eifs/base-blue_v3.0.0_linux_amd64.eif",
-                    "https://artifacts.marlin.org/oyster/eifs/base-blue_v3.0.0_linux_arm64.eif",
-                ];
-
-                if !allowed_urls.contains(&url.as_str()) {
+                if !config.allowed_image_urls.iter().any(|allowed| allowed == url) {
                     info!(
                         "Not using blue images for deployment. URL in metadata: {}",
                         url
@@ -136,6 +196,8 @@ async fn main() -> anyhow::Result<()> {
             }
 
             let pool_clone = pool.clone();
+            let notifiers_clone = notifiers.clone();
+            let config_clone = config.clone();
             tokio::spawn(async move {
                 info!("Handling JobOpened event:");
                 info!("job: {:?}", job);
@@ -146,28 +208,73 @@ async fn main() -> anyhow::Result<()> {
                     info!("instance: {}", instance);
                 }
 
-                info!("Waiting for 3 minutes for enclave to start...");
-                tokio::time::sleep(StdDuration::from_secs(180)).await;
-
-                let instance_ip = match wait_for_ip_address(
-                    &cp_url,
-                    job.clone(),
-                    metadata.region.as_deref().unwrap_or(""),
-                )
-                .await
-                {
-                    Ok(ip) => ip,
+                let operator_str = format!("{:?}", operator);
+
+                // Drive a single job_checks row through its lifecycle instead
+                // of only recording isolated error rows, so fleet health can
+                // be queried directly.
+                let mut job_check = match pool_clone.get() {
+                    Ok(mut conn) => match JobCheck::create(&mut conn, job.clone(), operator_str.clone())
+                    {
+                        Ok(check) => Some(check),
+                        Err(db_err) => {
+                            error!("Failed to create job_checks row: {}", db_err);
+                            None
+                        }
+                    },
                     Err(e) => {
-                        let error_msg = format!("Failed to get IP address: {}", e);
+                        error!("Failed to get DB connection for job_checks row: {}", e);
+                        None
+                    }
+                };
+
+                macro_rules! advance_job_check {
+                    ($state:expr, $ip:expr, $attempts:expr, $final_text:expr) => {
+                        if let Some(check) = &job_check {
+                            match pool_clone.get() {
+                                Ok(mut conn) => {
+                                    match check.advance(&mut conn, $state, $ip, $attempts, $final_text) {
+                                        Ok(updated) => job_check = Some(updated),
+                                        Err(db_err) => {
+                                            error!("Failed to advance job_checks row: {}", db_err)
+                                        }
+                                    }
+                                }
+                                Err(e) => error!("Failed to get DB connection to advance job_checks row: {}", e),
+                            }
+                        }
+                    };
+                }
+
+                info!(
+                    "Waiting {:?} for enclave to start...",
+                    config_clone.enclave_warmup
+                );
+                tokio::time::sleep(config_clone.enclave_warmup).await;
+
+                advance_job_check!(JobCheckState::WaitingForIp, None, 1, None);
+
+                let ip_attempt = retry_async(&config_clone.retry_policy, || {
+                    wait_for_ip_address(&cp_url, job.clone(), metadata.region.as_deref().unwrap_or(""))
+                })
+                .await;
+
+                let instance_ip = match ip_attempt {
+                    Ok(attempt) => attempt.value,
+                    Err(retry_err) => {
+                        let error_msg = format!(
+                            "Failed to get IP address after {} attempt(s): {}",
+                            retry_err.attempts, retry_err.error
+                        );
                         error!("{}", error_msg);
+                        metrics::IP_RESOLUTION_FAILURES_TOTAL.inc();
 
                         // Log error to database
-                        let operator_str = format!("{:?}", operator);
                         let new_error = NewReachabilityError::new(
                             job.clone(),
-                            operator_str,
+                            operator_str.clone(),
                             "N/A".to_string(),
-                            error_msg,
+                            error_msg.clone(),
                         );
 
                         if let Ok(mut conn) = pool_clone.get() {
@@ -175,104 +282,153 @@ async fn main() -> anyhow::Result<()> {
                                 error!("Failed to insert error into database: {}", db_err);
                             }
                         }
+
+                        let event = MonitorEvent::new(
+                            job.clone(),
+                            operator_str.clone(),
+                            "N/A".to_string(),
+                            FailureKind::IpTimeout,
+                            error_msg.clone(),
+                        );
+                        notifier::notify_all(&notifiers_clone, &event).await;
+
+                        advance_job_check!(
+                            JobCheckState::Failed,
+                            None,
+                            retry_err.attempts as i32,
+                            Some(error_msg)
+                        );
                         return;
                     }
                 };
 
                 info!("instance IP: {}", instance_ip);
 
-                if check_reachability(&instance_ip).await {
-                    info!("Instance is reachable");
-                } else {
-                    let error_msg = "Instance reachability test failed";
-                    error!("{}", error_msg);
-
-                    // Log error to database
-                    let operator_str = format!("{:?}", operator);
-                    let new_error = NewReachabilityError::new(
-                        job.clone(),
-                        operator_str,
-                        instance_ip.clone(),
-                        error_msg.to_string(),
-                    );
+                advance_job_check!(
+                    JobCheckState::CheckingReachability,
+                    Some(instance_ip.clone()),
+                    1,
+                    None
+                );
+
+                let mut failure_reason: Option<String> = None;
+                let reachability_attempts: i32;
+                let refresh_attempts: i32;
+
+                let reachability_attempt = retry_async(&config_clone.retry_policy, || async {
+                    let timer = metrics::REACHABILITY_CHECK_DURATION_SECONDS.start_timer();
+                    let is_reachable = check_reachability(&instance_ip).await;
+                    timer.observe_duration();
+
+                    if is_reachable {
+                        metrics::REACHABILITY_CHECKS_TOTAL
+                            .with_label_values(&["success"])
+                            .inc();
+                        Ok(())
+                    } else {
+                        metrics::REACHABILITY_CHECKS_TOTAL
+                            .with_label_values(&["failure"])
+                            .inc();
+                        Err("Instance reachability test failed")
+                    }
+                })
+                .await;
+
+                match reachability_attempt {
+                    Ok(attempt) => {
+                        reachability_attempts = attempt.attempts as i32;
+                        info!("Instance is reachable");
+                    }
+                    Err(retry_err) => {
+                        reachability_attempts = retry_err.attempts as i32;
+                        let error_msg = format!(
+                            "{} after {} attempt(s)",
+                            retry_err.error, retry_err.attempts
+                        );
+                        error!("{}", error_msg);
 
-                    if let Ok(mut conn) = pool_clone.get() {
-                        if let Err(db_err) = new_error.insert(&mut conn) {
-                            error!("Failed to insert error into database: {}", db_err);
+                        // Log error to database
+                        let new_error = NewReachabilityError::new(
+                            job.clone(),
+                            operator_str.clone(),
+                            instance_ip.clone(),
+                            error_msg.clone(),
+                        );
+
+                        if let Ok(mut conn) = pool_clone.get() {
+                            if let Err(db_err) = new_error.insert(&mut conn) {
+                                error!("Failed to insert error into database: {}", db_err);
+                            }
                         }
+
+                        let event = MonitorEvent::new(
+                            job.clone(),
+                            operator_str.clone(),
+                            instance_ip.clone(),
+                            FailureKind::ReachabilityFailed,
+                            error_msg.clone(),
+                        );
+                        notifier::notify_all(&notifiers_clone, &event).await;
+
+                        failure_reason = Some(error_msg);
                     }
                 }
 
-                // Call the refresh API to verify IP is available
+                advance_job_check!(
+                    JobCheckState::CheckingEndpoint,
+                    Some(instance_ip.clone()),
+                    reachability_attempts,
+                    None
+                );
+
+                // Call the refresh API to verify IP is available, retrying the
+                // whole call+parse+check sequence on transient failures.
                 let refresh_url = format!(
-                    "https://sk.arb1.marlin.org/operators/jobs/refresh/ArbOne/{}",
-                    job
+                    "{}/operators/jobs/refresh/ArbOne/{}",
+                    config_clone.refresh_base_url, job
                 );
-                info!("Calling refresh API: {}", refresh_url);
-
-                let client = reqwest::Client::new();
-                match client.get(&refresh_url).send().await {
-                    Ok(response) => {
-                        match response.json::<serde_json::Value>().await {
-                            Ok(json) => {
-                                if json.get("ip").is_some() {
-                                    info!("IP key found in refresh API response");
-                                } else {
-                                    let error_msg = "IP key NOT found in refresh API response";
-                                    error!("{}", error_msg);
-
-                                    // Log error to database
-                                    let operator_str = format!("{:?}", operator);
-                                    let new_error = NewOperatorEndpointError::new(
-                                        job.clone(),
-                                        operator_str,
-                                        instance_ip.clone(),
-                                        error_msg.to_string(),
-                                    );
-
-                                    if let Ok(mut conn) = pool_clone.get() {
-                                        if let Err(db_err) = new_error.insert(&mut conn) {
-                                            error!(
-                                                "Failed to insert error into database: {}",
-                                                db_err
-                                            );
-                                        }
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                let error_msg =
-                                    format!("Failed to parse refresh API response: {}", e);
-                                error!("{}", error_msg);
-
-                                // Log error to database
-                                let operator_str = format!("{:?}", operator);
-                                let new_error = NewOperatorEndpointError::new(
-                                    job.clone(),
-                                    operator_str,
-                                    instance_ip.clone(),
-                                    error_msg,
-                                );
-
-                                if let Ok(mut conn) = pool_clone.get() {
-                                    if let Err(db_err) = new_error.insert(&mut conn) {
-                                        error!("Failed to insert error into database: {}", db_err);
-                                    }
-                                }
-                            }
-                        }
+
+                let refresh_attempt = retry_async(&config_clone.retry_policy, || async {
+                    info!("Calling refresh API: {}", refresh_url);
+                    let response = reqwest::Client::new()
+                        .get(&refresh_url)
+                        .send()
+                        .await
+                        .map_err(|e| format!("Failed to call refresh API: {}", e))?;
+
+                    let json = response
+                        .json::<serde_json::Value>()
+                        .await
+                        .map_err(|e| format!("Failed to parse refresh API response: {}", e))?;
+
+                    if json.get("ip").is_some() {
+                        Ok(())
+                    } else {
+                        Err("IP key NOT found in refresh API response".to_string())
                     }
-                    Err(e) => {
-                        let error_msg = format!("Failed to call refresh API: {}", e);
+                })
+                .await;
+
+                match refresh_attempt {
+                    Ok(attempt) => {
+                        refresh_attempts = attempt.attempts as i32;
+                        info!("IP key found in refresh API response");
+                    }
+                    Err(retry_err) => {
+                        refresh_attempts = retry_err.attempts as i32;
+                        let error_msg = format!(
+                            "{} after {} attempt(s)",
+                            retry_err.error, retry_err.attempts
+                        );
                         error!("{}", error_msg);
+                        metrics::REFRESH_API_FAILURES_TOTAL.inc();
 
                         // Log error to database
-                        let operator_str = format!("{:?}", operator);
                         let new_error = NewOperatorEndpointError::new(
                             job.clone(),
-                            operator_str,
+                            operator_str.clone(),
                             instance_ip.clone(),
-                            error_msg,
+                            error_msg.clone(),
                         );
 
                         if let Ok(mut conn) = pool_clone.get() {
@@ -280,12 +436,67 @@ async fn main() -> anyhow::Result<()> {
                                 error!("Failed to insert error into database: {}", db_err);
                             }
                         }
+
+                        let event = MonitorEvent::new(
+                            job.clone(),
+                            operator_str.clone(),
+                            instance_ip.clone(),
+                            FailureKind::RefreshMissingIp,
+                            error_msg.clone(),
+                        );
+                        notifier::notify_all(&notifiers_clone, &event).await;
+
+                        failure_reason.get_or_insert(error_msg);
+                    }
+                }
+
+                // Write the terminal disposition once, after every check has run.
+                match failure_reason {
+                    Some(reason) => {
+                        advance_job_check!(
+                            JobCheckState::Failed,
+                            Some(instance_ip.clone()),
+                            refresh_attempts,
+                            Some(reason)
+                        );
+                    }
+                    None => {
+                        advance_job_check!(
+                            JobCheckState::Healthy,
+                            Some(instance_ip.clone()),
+                            refresh_attempts,
+                            None
+                        );
                     }
                 }
             });
         }
 
-        // Update last checked block
-        last_checked_block = current_block;
+        // Only checkpoint the confirmed portion of the range; the trailing
+        // `confirmation_depth` blocks stay un-checkpointed and are re-scanned
+        // next tick in case of a reorg.
+        if safe_block > last_checked_block {
+            match pool.get() {
+                Ok(mut conn) => {
+                    if let Err(e) = ScanState::save(
+                        &mut conn,
+                        chain_id,
+                        &contract_address_str,
+                        safe_block.as_u64() as i64,
+                    ) {
+                        error!("Failed to persist scan checkpoint: {}", e);
+                    }
+                    // Once a block is behind the checkpoint it can never be
+                    // re-scanned, so its dedup markers are safe to drop.
+                    if let Err(e) = ProcessedLog::prune_up_to(&mut conn, safe_block.as_u64() as i64)
+                    {
+                        error!("Failed to prune processed log markers: {}", e);
+                    }
+                }
+                Err(e) => error!("Failed to get DB connection to persist checkpoint: {}", e),
+            }
+            last_checked_block = safe_block;
+            metrics::LAST_CHECKED_BLOCK.set(last_checked_block.as_u64() as i64);
+        }
     }
 }
@@ -0,0 +1,100 @@
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server};
+use log::{error, info};
+use once_cell::sync::Lazy;
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge, Opts, Registry,
+    TextEncoder,
+};
+use std::net::SocketAddr;
+
+static REGISTRY: Lazy<Registry> = Lazy::new(Registry::new);
+
+pub static JOBS_OBSERVED_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
+    let counter =
+        IntCounter::new("jobs_observed_total", "Total JobOpened events observed").unwrap();
+    REGISTRY.register(Box::new(counter.clone())).unwrap();
+    counter
+});
+
+pub static REACHABILITY_CHECKS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new(
+            "reachability_checks_total",
+            "Total reachability checks, labeled by result",
+        ),
+        &["result"],
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(counter.clone())).unwrap();
+    counter
+});
+
+pub static IP_RESOLUTION_FAILURES_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
+    let counter = IntCounter::new(
+        "ip_resolution_failures_total",
+        "Total failures to resolve an instance IP",
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(counter.clone())).unwrap();
+    counter
+});
+
+pub static REFRESH_API_FAILURES_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
+    let counter = IntCounter::new(
+        "refresh_api_failures_total",
+        "Total refresh API call/response failures",
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(counter.clone())).unwrap();
+    counter
+});
+
+pub static REACHABILITY_CHECK_DURATION_SECONDS: Lazy<Histogram> = Lazy::new(|| {
+    let histogram = Histogram::with_opts(HistogramOpts::new(
+        "reachability_check_duration_seconds",
+        "Time spent running check_reachability",
+    ))
+    .unwrap();
+    REGISTRY.register(Box::new(histogram.clone())).unwrap();
+    histogram
+});
+
+pub static LAST_CHECKED_BLOCK: Lazy<IntGauge> = Lazy::new(|| {
+    let gauge = IntGauge::new(
+        "last_checked_block",
+        "Last block number checkpointed by the scan loop",
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(gauge.clone())).unwrap();
+    gauge
+});
+
+async fn serve_metrics(_req: Request<Body>) -> Result<Response<Body>, hyper::Error> {
+    let encoder = TextEncoder::new();
+    let metric_families = REGISTRY.gather();
+    let mut buffer = Vec::new();
+    encoder.encode(&metric_families, &mut buffer).unwrap();
+    Ok(Response::new(Body::from(buffer)))
+}
+
+/// Registers every metric and spawns the `/metrics` HTTP server on `addr`
+/// alongside the polling loop.
+pub fn start(addr: SocketAddr) {
+    Lazy::force(&JOBS_OBSERVED_TOTAL);
+    Lazy::force(&REACHABILITY_CHECKS_TOTAL);
+    Lazy::force(&IP_RESOLUTION_FAILURES_TOTAL);
+    Lazy::force(&REFRESH_API_FAILURES_TOTAL);
+    Lazy::force(&REACHABILITY_CHECK_DURATION_SECONDS);
+    Lazy::force(&LAST_CHECKED_BLOCK);
+
+    tokio::spawn(async move {
+        let make_svc =
+            make_service_fn(|_conn| async { Ok::<_, hyper::Error>(service_fn(serve_metrics)) });
+
+        info!("Serving Prometheus metrics on {}", addr);
+        if let Err(e) = Server::bind(&addr).serve(make_svc).await {
+            error!("Metrics server error: {}", e);
+        }
+    });
+}
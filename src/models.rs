@@ -1,6 +1,15 @@
-use crate::schema::{operator_endpoint_errors, reachability_errors};
+use crate::schema::{
+    job_checks, operator_endpoint_errors, processed_logs, reachability_errors, scan_state,
+};
 use diesel::prelude::*;
 
+fn now_secs() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs() as i64
+}
+
 #[derive(Queryable, Selectable, Debug)]
 #[diesel(table_name = reachability_errors)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
@@ -90,3 +99,249 @@ impl NewOperatorEndpointError {
             .get_result(conn)
     }
 }
+
+/// Tracks the last block fully scanned for a given chain/contract pair, so a
+/// restart resumes the poll loop instead of re-seeding from the current head.
+#[derive(Queryable, Selectable, Debug)]
+#[diesel(table_name = scan_state)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ScanState {
+    pub id: i64,
+    pub chain_id: i64,
+    pub contract_address: String,
+    pub last_checked_block: i64,
+    pub updated_time: i64,
+}
+
+#[derive(Insertable, AsChangeset)]
+#[diesel(table_name = scan_state)]
+pub struct NewScanState {
+    pub chain_id: i64,
+    pub contract_address: String,
+    pub last_checked_block: i64,
+    pub updated_time: i64,
+}
+
+impl ScanState {
+    /// Loads the stored checkpoint for `chain_id`/`contract_address`, if any.
+    pub fn load(
+        conn: &mut PgConnection,
+        chain_id: i64,
+        address: &str,
+    ) -> QueryResult<Option<ScanState>> {
+        scan_state::table
+            .filter(scan_state::chain_id.eq(chain_id))
+            .filter(scan_state::contract_address.eq(address))
+            .first(conn)
+            .optional()
+    }
+
+    /// Persists `block` as the new checkpoint, upserting on the
+    /// `(chain_id, contract_address)` pair.
+    pub fn save(
+        conn: &mut PgConnection,
+        chain_id: i64,
+        address: &str,
+        block: i64,
+    ) -> QueryResult<ScanState> {
+        let updated_time = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs() as i64;
+
+        let new_state = NewScanState {
+            chain_id,
+            contract_address: address.to_string(),
+            last_checked_block: block,
+            updated_time,
+        };
+
+        diesel::insert_into(scan_state::table)
+            .values(&new_state)
+            .on_conflict((scan_state::chain_id, scan_state::contract_address))
+            .do_update()
+            .set(&new_state)
+            .get_result(conn)
+    }
+}
+
+/// The lifecycle states a single job passes through on its way from
+/// discovery to a final disposition, mirroring a CI job's
+/// Pending -> Started -> Finished progression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobCheckState {
+    Pending,
+    WaitingForIp,
+    CheckingReachability,
+    CheckingEndpoint,
+    Healthy,
+    Failed,
+}
+
+impl JobCheckState {
+    fn as_str(self) -> &'static str {
+        match self {
+            JobCheckState::Pending => "pending",
+            JobCheckState::WaitingForIp => "waiting_for_ip",
+            JobCheckState::CheckingReachability => "checking_reachability",
+            JobCheckState::CheckingEndpoint => "checking_endpoint",
+            JobCheckState::Healthy => "healthy",
+            JobCheckState::Failed => "failed",
+        }
+    }
+}
+
+impl std::fmt::Display for JobCheckState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for JobCheckState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(JobCheckState::Pending),
+            "waiting_for_ip" => Ok(JobCheckState::WaitingForIp),
+            "checking_reachability" => Ok(JobCheckState::CheckingReachability),
+            "checking_endpoint" => Ok(JobCheckState::CheckingEndpoint),
+            "healthy" => Ok(JobCheckState::Healthy),
+            "failed" => Ok(JobCheckState::Failed),
+            other => Err(format!("unknown job check state: {other}")),
+        }
+    }
+}
+
+#[derive(Queryable, Selectable, Debug)]
+#[diesel(table_name = job_checks)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct JobCheck {
+    pub id: i64,
+    pub job: String,
+    pub operator: String,
+    pub ip: Option<String>,
+    pub state: String,
+    pub attempts: i32,
+    pub final_text: Option<String>,
+    pub created_time: i64,
+    pub updated_time: i64,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = job_checks)]
+pub struct NewJobCheck {
+    pub job: String,
+    pub operator: String,
+    pub ip: Option<String>,
+    pub state: String,
+    pub attempts: i32,
+    pub final_text: Option<String>,
+    pub created_time: i64,
+    pub updated_time: i64,
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name = job_checks)]
+struct JobCheckTransition {
+    ip: Option<String>,
+    state: String,
+    attempts: i32,
+    final_text: Option<String>,
+    updated_time: i64,
+}
+
+impl JobCheck {
+    /// Inserts a new row in the `Pending` state for a job that was just
+    /// discovered via a `JobOpened` event.
+    pub fn create(conn: &mut PgConnection, job: String, operator: String) -> QueryResult<JobCheck> {
+        let now = now_secs();
+        let new_check = NewJobCheck {
+            job,
+            operator,
+            ip: None,
+            state: JobCheckState::Pending.to_string(),
+            attempts: 0,
+            final_text: None,
+            created_time: now,
+            updated_time: now,
+        };
+
+        diesel::insert_into(job_checks::table)
+            .values(&new_check)
+            .get_result(conn)
+    }
+
+    /// Moves this row to `state`, overwriting `ip`, `attempts` and
+    /// `final_text` and updating `updated_time`. The service sets
+    /// `final_text` to the failure reason on `Failed` and leaves it `None`
+    /// on every other state, `Healthy` included.
+    pub fn advance(
+        &self,
+        conn: &mut PgConnection,
+        state: JobCheckState,
+        ip: Option<String>,
+        attempts: i32,
+        final_text: Option<String>,
+    ) -> QueryResult<JobCheck> {
+        let transition = JobCheckTransition {
+            ip,
+            state: state.to_string(),
+            attempts,
+            final_text,
+            updated_time: now_secs(),
+        };
+
+        diesel::update(job_checks::table.find(self.id))
+            .set(&transition)
+            .get_result(conn)
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = processed_logs)]
+struct NewProcessedLog {
+    job: String,
+    block_number: i64,
+    log_index: i64,
+    processed_time: i64,
+}
+
+/// Records which `(job, block, log_index)` logs have already been handled,
+/// persisted so the trailing confirmation window that gets re-scanned on
+/// every tick, and again after a restart, doesn't re-spawn work for events
+/// already processed.
+pub struct ProcessedLog;
+
+impl ProcessedLog {
+    /// Attempts to record a log as processed. Returns `true` the first time
+    /// a given `(job, block_number, log_index)` is seen, and `false` if it
+    /// was already recorded, so the caller can skip re-handling it.
+    pub fn try_mark_processed(
+        conn: &mut PgConnection,
+        job: &str,
+        block_number: i64,
+        log_index: i64,
+    ) -> QueryResult<bool> {
+        let new_log = NewProcessedLog {
+            job: job.to_string(),
+            block_number,
+            log_index,
+            processed_time: now_secs(),
+        };
+
+        let inserted = diesel::insert_into(processed_logs::table)
+            .values(&new_log)
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+
+        Ok(inserted > 0)
+    }
+
+    /// Deletes records for blocks at or below `max_block`, since the scan
+    /// loop will never re-enter blocks that far behind the checkpoint.
+    pub fn prune_up_to(conn: &mut PgConnection, max_block: i64) -> QueryResult<usize> {
+        diesel::delete(processed_logs::table.filter(processed_logs::block_number.le(max_block)))
+            .execute(conn)
+    }
+}
@@ -0,0 +1,246 @@
+use async_trait::async_trait;
+use log::{error, warn};
+use serde::Deserialize;
+
+/// The stage at which a job failed, so notifications can be filtered or
+/// routed differently per kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    IpTimeout,
+    ReachabilityFailed,
+    RefreshMissingIp,
+}
+
+impl FailureKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            FailureKind::IpTimeout => "ip_timeout",
+            FailureKind::ReachabilityFailed => "reachability_failed",
+            FailureKind::RefreshMissingIp => "refresh_missing_ip",
+        }
+    }
+}
+
+/// A single monitoring failure, carrying everything a notifier needs to
+/// render a useful message.
+#[derive(Debug, Clone)]
+pub struct MonitorEvent {
+    pub job: String,
+    pub operator: String,
+    pub ip: String,
+    pub kind: FailureKind,
+    pub message: String,
+    pub timestamp: i64,
+}
+
+impl MonitorEvent {
+    pub fn new(
+        job: String,
+        operator: String,
+        ip: String,
+        kind: FailureKind,
+        message: String,
+    ) -> Self {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs() as i64;
+
+        Self {
+            job,
+            operator,
+            ip,
+            kind,
+            message,
+            timestamp,
+        }
+    }
+}
+
+/// A sink that a `MonitorEvent` can be fanned out to. Implementations should
+/// log their own send failures rather than propagating them, since a
+/// notification failure must never take down the monitoring loop.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn notify(&self, event: &MonitorEvent);
+}
+
+/// Posts the event as JSON to a generic HTTP endpoint.
+pub struct WebhookNotifier {
+    url: String,
+    client: reqwest::Client,
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    async fn notify(&self, event: &MonitorEvent) {
+        let body = serde_json::json!({
+            "job": event.job,
+            "operator": event.operator,
+            "ip": event.ip,
+            "kind": event.kind.as_str(),
+            "message": event.message,
+            "timestamp": event.timestamp,
+        });
+
+        if let Err(e) = self.client.post(&self.url).json(&body).send().await {
+            error!("Webhook notifier failed to send to {}: {}", self.url, e);
+        }
+    }
+}
+
+/// Posts a formatted message to a Slack incoming webhook.
+pub struct SlackNotifier {
+    webhook_url: String,
+    client: reqwest::Client,
+}
+
+#[async_trait]
+impl Notifier for SlackNotifier {
+    async fn notify(&self, event: &MonitorEvent) {
+        let text = format!(
+            "*{}* for job `{}` (operator {}, ip {}): {}",
+            event.kind.as_str(),
+            event.job,
+            event.operator,
+            event.ip,
+            event.message
+        );
+
+        if let Err(e) = self
+            .client
+            .post(&self.webhook_url)
+            .json(&serde_json::json!({ "text": text }))
+            .send()
+            .await
+        {
+            error!("Slack notifier failed to send: {}", e);
+        }
+    }
+}
+
+/// Posts a text message to a Matrix room via the client-server API.
+pub struct MatrixNotifier {
+    homeserver_url: String,
+    room_id: String,
+    access_token: String,
+    client: reqwest::Client,
+}
+
+#[async_trait]
+impl Notifier for MatrixNotifier {
+    async fn notify(&self, event: &MonitorEvent) {
+        let url = format!(
+            "{}/_matrix/client/r0/rooms/{}/send/m.room.message",
+            self.homeserver_url, self.room_id
+        );
+        let body = serde_json::json!({
+            "msgtype": "m.text",
+            "body": format!(
+                "{} for job {} (operator {}, ip {}): {}",
+                event.kind.as_str(), event.job, event.operator, event.ip, event.message
+            ),
+        });
+
+        if let Err(e) = self
+            .client
+            .post(&url)
+            .bearer_auth(&self.access_token)
+            .json(&body)
+            .send()
+            .await
+        {
+            error!("Matrix notifier failed to send: {}", e);
+        }
+    }
+}
+
+/// One entry in the notifier config file, tagged by `type`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum NotifierTargetConfig {
+    Webhook {
+        url: String,
+    },
+    Slack {
+        webhook_url: String,
+    },
+    Matrix {
+        homeserver_url: String,
+        room_id: String,
+        access_token: String,
+    },
+}
+
+/// Top-level shape of the file pointed to by `NOTIFIER_CONFIG_PATH`.
+#[derive(Debug, Deserialize)]
+struct NotifierConfig {
+    notifiers: Vec<NotifierTargetConfig>,
+}
+
+impl NotifierConfig {
+    fn load(path: &str) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        if path.ends_with(".toml") {
+            Ok(toml::from_str(&contents)?)
+        } else {
+            Ok(serde_json::from_str(&contents)?)
+        }
+    }
+
+    fn build(&self) -> Vec<Box<dyn Notifier>> {
+        self.notifiers
+            .iter()
+            .map(|target| -> Box<dyn Notifier> {
+                match target {
+                    NotifierTargetConfig::Webhook { url } => Box::new(WebhookNotifier {
+                        url: url.clone(),
+                        client: reqwest::Client::new(),
+                    }),
+                    NotifierTargetConfig::Slack { webhook_url } => Box::new(SlackNotifier {
+                        webhook_url: webhook_url.clone(),
+                        client: reqwest::Client::new(),
+                    }),
+                    NotifierTargetConfig::Matrix {
+                        homeserver_url,
+                        room_id,
+                        access_token,
+                    } => Box::new(MatrixNotifier {
+                        homeserver_url: homeserver_url.clone(),
+                        room_id: room_id.clone(),
+                        access_token: access_token.clone(),
+                        client: reqwest::Client::new(),
+                    }),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Builds the configured notifiers from the file at `path`. Returns an
+/// empty list (with a warning/error logged) if `path` is `None` or the file
+/// can't be loaded, so notification failures never stop the service from
+/// starting.
+pub fn load(path: Option<&str>) -> Vec<Box<dyn Notifier>> {
+    match path {
+        Some(path) => match NotifierConfig::load(path) {
+            Ok(config) => config.build(),
+            Err(e) => {
+                error!("Failed to load notifier config from {}: {}", path, e);
+                Vec::new()
+            }
+        },
+        None => {
+            warn!("notifier_config_path not set; no notifiers configured");
+            Vec::new()
+        }
+    }
+}
+
+/// Fans `event` out to every configured notifier, logging but swallowing
+/// individual send failures.
+pub async fn notify_all(notifiers: &[Box<dyn Notifier>], event: &MonitorEvent) {
+    for notifier in notifiers {
+        notifier.notify(event).await;
+    }
+}
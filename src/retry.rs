@@ -0,0 +1,64 @@
+use log::warn;
+use std::future::Future;
+use std::time::Duration;
+
+/// Bounded exponential backoff: `base_delay`, doubled (or scaled by
+/// `multiplier`) after each failed attempt up to `max_delay`, for at most
+/// `max_attempts` attempts total.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub multiplier: f64,
+    pub max_delay: Duration,
+}
+
+/// A successful result along with the number of attempts it took.
+#[derive(Debug)]
+pub struct Attempted<T> {
+    pub value: T,
+    pub attempts: u32,
+}
+
+/// The error from the final attempt, along with the total attempts made.
+#[derive(Debug)]
+pub struct RetryError<E> {
+    pub error: E,
+    pub attempts: u32,
+}
+
+/// Runs `op` under `policy`, retrying with exponential backoff until it
+/// succeeds or `max_attempts` is reached. Shared by the reachability path
+/// and the refresh-API path so a slow-but-healthy enclave doesn't get
+/// recorded as a hard failure on its first check.
+pub async fn retry_async<T, E, F, Fut>(
+    policy: &RetryPolicy,
+    mut op: F,
+) -> Result<Attempted<T>, RetryError<E>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut delay = policy.base_delay;
+    let mut attempts = 0;
+
+    loop {
+        attempts += 1;
+        match op().await {
+            Ok(value) => return Ok(Attempted { value, attempts }),
+            Err(error) => {
+                if attempts >= policy.max_attempts {
+                    return Err(RetryError { error, attempts });
+                }
+
+                warn!(
+                    "Attempt {}/{} failed, retrying in {:?}",
+                    attempts, policy.max_attempts, delay
+                );
+                tokio::time::sleep(delay).await;
+                delay = Duration::from_secs_f64(delay.as_secs_f64() * policy.multiplier)
+                    .min(policy.max_delay);
+            }
+        }
+    }
+}
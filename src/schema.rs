@@ -19,3 +19,37 @@ diesel::table! {
         timestamp -> Int8,
     }
 }
+
+diesel::table! {
+    job_checks (id) {
+        id -> Int8,
+        job -> Varchar,
+        operator -> Varchar,
+        ip -> Nullable<Varchar>,
+        state -> Varchar,
+        attempts -> Int4,
+        final_text -> Nullable<Varchar>,
+        created_time -> Int8,
+        updated_time -> Int8,
+    }
+}
+
+diesel::table! {
+    scan_state (id) {
+        id -> Int8,
+        chain_id -> Int8,
+        contract_address -> Varchar,
+        last_checked_block -> Int8,
+        updated_time -> Int8,
+    }
+}
+
+diesel::table! {
+    processed_logs (id) {
+        id -> Int8,
+        job -> Varchar,
+        block_number -> Int8,
+        log_index -> Int8,
+        processed_time -> Int8,
+    }
+}